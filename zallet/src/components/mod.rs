@@ -0,0 +1 @@
+pub mod safe_environment;
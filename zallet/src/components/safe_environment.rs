@@ -6,8 +6,41 @@
 //! https://github.com/rust-cli/config-rs/pull/683
 
 use std::env;
+use std::ffi::OsString;
 
 use config::{ConfigError, Map, Source, Value, ValueKind};
+#[cfg(feature = "convert-case")]
+use convert_case::{Case, Casing};
+
+/// Policy for a key or value that isn't valid Unicode (default: [`OnNonUnicode::Ignore`])
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnNonUnicode {
+    /// Silently drop the variable, as if it were never set (matches the
+    /// pre-existing behavior)
+    #[default]
+    Ignore,
+    /// Capture the key or value anyway via [`OsString::to_string_lossy`]
+    Lossy,
+    /// Return a [`ConfigError`] naming the offending variable
+    Error,
+}
+
+/// A type a resolved key can be pinned to via
+/// [`SafeEnvironment::with_parse_override`], skipping the speculative
+/// bool/i64/f64 parse chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseKind {
+    /// Never parse, keep the raw string
+    String,
+    /// Parse with [`str::parse::<bool>`] (case-insensitive)
+    Bool,
+    /// Parse with [`str::parse::<i64>`]
+    I64,
+    /// Parse with [`str::parse::<f64>`]
+    F64,
+    /// Split on [`SafeEnvironment::list_separator`] into a list of strings
+    Array,
+}
 
 /// A safe environment source that prevents Unicode panics and race conditions
 ///
@@ -27,8 +60,32 @@ pub struct SafeEnvironment {
     list_separator: Option<String>,
     /// Keys that should be parsed as lists
     list_parse_keys: Option<Vec<String>>,
+    /// Case to re-encode each key segment into (default: none, i.e. lowercase only)
+    #[cfg(feature = "convert-case")]
+    key_case: Option<Case>,
+    /// Whether a blank value should be treated as if the variable were unset
+    ignore_empty: bool,
+    /// Policy applied to a key or value that isn't valid Unicode
+    on_non_unicode: OnNonUnicode,
+    /// Resolved keys pinned to a forced type, consulted before `try_parsing`
+    parse_overrides: Map<String, ParseKind>,
     /// Pre-filtered environment variables (safe, no race conditions)
-    filtered_env: Map<String, String>,
+    ///
+    /// Values are kept as `OsString`, and the key's own Unicode validity is
+    /// recorded, so a non-Unicode key or value can still be handled per
+    /// `on_non_unicode` in [`Source::collect`] instead of being
+    /// unconditionally discarded while taking the snapshot.
+    filtered_env: Map<String, FilteredEntry>,
+}
+
+/// A single pre-filtered environment variable
+#[derive(Clone, Debug)]
+struct FilteredEntry {
+    /// Whether the raw key was valid Unicode (the map key is otherwise a
+    /// lossy decoding, used only so `filter_fn` had something to match against)
+    key_is_unicode: bool,
+    /// The raw value, not yet resolved against `on_non_unicode`
+    value: OsString,
 }
 
 impl SafeEnvironment {
@@ -43,19 +100,23 @@ impl SafeEnvironment {
 
         // Single atomic snapshot - no race condition
         for (key, value) in env::vars_os() {
-            // Safe Unicode conversion - no panic
-            if let Some(key_str) = key.to_str() {
-                if let Some(stripped) = key_str.strip_prefix(&format!("{prefix}_")) {
-                    if filter_fn(stripped) {
-                        // Only convert to String after we know it's valid Unicode
-                        if let Some(value_str) = value.to_str() {
-                            filtered_env.insert(key_str.to_owned(), value_str.to_owned());
-                        }
-                        // Non-Unicode values are silently ignored
-                    }
+            // Lossily decode just so `filter_fn` has a `&str` to match
+            // against; whether a non-Unicode key is dropped, kept under its
+            // lossy name, or rejected is decided by `on_non_unicode` when the
+            // source is collected.
+            let key_is_unicode = key.to_str().is_some();
+            let key_str = key.to_string_lossy();
+            if let Some(stripped) = key_str.strip_prefix(&format!("{prefix}_")) {
+                if filter_fn(stripped) {
+                    filtered_env.insert(
+                        key_str.into_owned(),
+                        FilteredEntry {
+                            key_is_unicode,
+                            value,
+                        },
+                    );
                 }
             }
-            // Non-Unicode keys are silently ignored
         }
 
         Ok(Self {
@@ -65,6 +126,11 @@ impl SafeEnvironment {
             try_parsing: true,
             list_separator: Some(",".to_owned()),
             list_parse_keys: None,
+            #[cfg(feature = "convert-case")]
+            key_case: None,
+            ignore_empty: false,
+            on_non_unicode: OnNonUnicode::default(),
+            parse_overrides: Map::new(),
             filtered_env,
         })
     }
@@ -99,6 +165,49 @@ impl SafeEnvironment {
         keys.push(key.to_owned());
         self
     }
+
+    /// Re-encode each key segment into `case` instead of leaving it lowercased
+    /// (default: disabled, keeping the existing lowercase-only behavior)
+    ///
+    /// The translation is applied per path segment, after the prefix is
+    /// stripped and the key is split on [`Self::separator`], so the `.`
+    /// segment separators themselves are never affected.
+    #[cfg(feature = "convert-case")]
+    pub fn translate_key(mut self, case: Case) -> Self {
+        self.key_case = Some(case);
+        self
+    }
+
+    /// Treat blank values as if the variable were never set (default: false)
+    ///
+    /// This skips the variable in [`Source::collect`], so a lower-priority
+    /// source (a config file, a default) wins instead of being clobbered by
+    /// an accidentally-empty env var.
+    pub fn ignore_empty(mut self, ignore_empty: bool) -> Self {
+        self.ignore_empty = ignore_empty;
+        self
+    }
+
+    /// Set the policy for a key or value that isn't valid Unicode (default: [`OnNonUnicode::Ignore`])
+    pub fn on_non_unicode(mut self, policy: OnNonUnicode) -> Self {
+        self.on_non_unicode = policy;
+        self
+    }
+
+    /// Pin a resolved key (the dotted key after prefix/separator processing,
+    /// not the raw environment variable name) to `kind` instead of letting
+    /// `try_parsing` guess its type
+    ///
+    /// This gives deterministic typing for values that look numeric or
+    /// boolean but aren't, such as a ZIP code, an account number, or a
+    /// version string like `"1.0"`. `key` is matched case-insensitively; if
+    /// [`Self::translate_key`] is also configured, give `key` in its
+    /// translated form (e.g. `"http-timeout"` for [`Case::Kebab`]), since
+    /// that's the resolved key the override is matched against.
+    pub fn with_parse_override(mut self, key: &str, kind: ParseKind) -> Self {
+        self.parse_overrides.insert(key.to_lowercase(), kind);
+        self
+    }
 }
 
 impl Source for SafeEnvironment {
@@ -110,7 +219,39 @@ impl Source for SafeEnvironment {
         let mut m = Map::new();
         let uri: String = "the environment".into();
 
-        for (key, value) in &self.filtered_env {
+        for (key, entry) in &self.filtered_env {
+            if !entry.key_is_unicode {
+                match self.on_non_unicode {
+                    OnNonUnicode::Ignore => continue,
+                    OnNonUnicode::Lossy => {
+                        // `key` is already the lossy decoding from the snapshot
+                    }
+                    OnNonUnicode::Error => {
+                        return Err(ConfigError::Message(format!(
+                            "environment variable `{key}` (lossily decoded) does not have a valid Unicode name"
+                        )));
+                    }
+                }
+            }
+
+            let value = match entry.value.to_str() {
+                Some(value) => value.to_owned(),
+                None => match self.on_non_unicode {
+                    OnNonUnicode::Ignore => continue,
+                    OnNonUnicode::Lossy => entry.value.to_string_lossy().into_owned(),
+                    OnNonUnicode::Error => {
+                        return Err(ConfigError::Message(format!(
+                            "environment variable `{key}` is not valid Unicode"
+                        )));
+                    }
+                },
+            };
+
+            // Treat a blank value as if the variable were never set
+            if self.ignore_empty && value.is_empty() {
+                continue;
+            }
+
             let mut processed_key = key.to_lowercase();
 
             // Remove prefix
@@ -119,12 +260,59 @@ impl Source for SafeEnvironment {
                 processed_key = processed_key[prefix_pattern.len()..].to_string();
             }
 
-            // Replace separator with dots
+            // Split on the separator and re-join the segments with dots,
+            // optionally re-encoding each segment into the configured case
             if !self.separator.is_empty() {
-                processed_key = processed_key.replace(&self.separator, ".");
+                #[cfg(feature = "convert-case")]
+                let key_case = self.key_case;
+                #[cfg(feature = "convert-case")]
+                if let Some(case) = key_case {
+                    processed_key = processed_key
+                        .split(self.separator.as_str())
+                        .map(|segment| segment.to_case(case))
+                        .collect::<Vec<_>>()
+                        .join(".");
+                } else {
+                    processed_key = processed_key.replace(&self.separator, ".");
+                }
+                #[cfg(not(feature = "convert-case"))]
+                {
+                    processed_key = processed_key.replace(&self.separator, ".");
+                }
             }
 
-            let processed_value = if self.try_parsing {
+            let processed_value = if let Some(kind) =
+                self.parse_overrides.get(&processed_key.to_lowercase())
+            {
+                match kind {
+                    ParseKind::String => ValueKind::String(value.clone()),
+                    ParseKind::Bool => {
+                        value.to_lowercase().parse::<bool>().map(ValueKind::Boolean).map_err(|_| {
+                            ConfigError::Message(format!(
+                                "key `{processed_key}` is pinned to bool but `{value}` is not one"
+                            ))
+                        })?
+                    }
+                    ParseKind::I64 => value.parse::<i64>().map(ValueKind::I64).map_err(|_| {
+                        ConfigError::Message(format!(
+                            "key `{processed_key}` is pinned to i64 but `{value}` is not one"
+                        ))
+                    })?,
+                    ParseKind::F64 => value.parse::<f64>().map(ValueKind::Float).map_err(|_| {
+                        ConfigError::Message(format!(
+                            "key `{processed_key}` is pinned to f64 but `{value}` is not one"
+                        ))
+                    })?,
+                    ParseKind::Array => {
+                        let separator = self.list_separator.as_deref().unwrap_or(",");
+                        let v: Vec<Value> = value
+                            .split(separator)
+                            .map(|s| Value::new(Some(&uri), ValueKind::String(s.to_owned())))
+                            .collect();
+                        ValueKind::Array(v)
+                    }
+                }
+            } else if self.try_parsing {
                 // Try parsing as primitives first
                 if let Ok(parsed) = value.to_lowercase().parse::<bool>() {
                     ValueKind::Boolean(parsed)
@@ -160,3 +348,205 @@ impl Source for SafeEnvironment {
         Ok(m)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{LazyLock, Mutex};
+
+    /// `env::set_var`/`remove_var` mutate process-global state, so serialize
+    /// the tests that touch them to avoid racing across threads.
+    static ENV_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+    #[test]
+    #[cfg(feature = "convert-case")]
+    fn translate_key_recases_each_segment() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("TRCASE__HTTP_TIMEOUT", "30");
+
+        let collected = SafeEnvironment::with_prefix_and_filter("TRCASE", |_| true)
+            .unwrap()
+            .translate_key(Case::Kebab)
+            .collect();
+
+        env::remove_var("TRCASE__HTTP_TIMEOUT");
+
+        let collected = collected.unwrap();
+        assert!(collected.contains_key("http-timeout"));
+    }
+
+    #[test]
+    fn ignore_empty_drops_blank_vars_but_keeps_others() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("IGNEMPTY_HOST", "");
+        env::set_var("IGNEMPTY_PORT", "5432");
+
+        let collected = SafeEnvironment::with_prefix_and_filter("IGNEMPTY", |_| true)
+            .unwrap()
+            .ignore_empty(true)
+            .collect();
+
+        env::remove_var("IGNEMPTY_HOST");
+        env::remove_var("IGNEMPTY_PORT");
+
+        let collected = collected.unwrap();
+        assert!(!collected.contains_key("host"));
+        assert_eq!(collected.get("port").unwrap().clone().into_int().unwrap(), 5432);
+    }
+
+    #[test]
+    fn ignore_empty_disabled_keeps_blank_vars() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("IGNEMPTYOFF_HOST", "");
+
+        let collected = SafeEnvironment::with_prefix_and_filter("IGNEMPTYOFF", |_| true)
+            .unwrap()
+            .collect();
+
+        env::remove_var("IGNEMPTYOFF_HOST");
+
+        let collected = collected.unwrap();
+        assert_eq!(collected.get("host").unwrap().clone().into_string().unwrap(), "");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn on_non_unicode_value_ignore_lossy_error() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("NUVAL_PATH", OsString::from_vec(vec![b'/', 0xff, b'x']));
+
+        let ignored = SafeEnvironment::with_prefix_and_filter("NUVAL", |_| true)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert!(!ignored.contains_key("path"));
+
+        let lossy = SafeEnvironment::with_prefix_and_filter("NUVAL", |_| true)
+            .unwrap()
+            .on_non_unicode(OnNonUnicode::Lossy)
+            .collect()
+            .unwrap();
+        let lossy_value = lossy.get("path").unwrap().clone().into_string().unwrap();
+        assert!(lossy_value.contains('\u{FFFD}'));
+
+        let errored = SafeEnvironment::with_prefix_and_filter("NUVAL", |_| true)
+            .unwrap()
+            .on_non_unicode(OnNonUnicode::Error)
+            .collect();
+
+        env::remove_var("NUVAL_PATH");
+
+        assert!(errored.is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn on_non_unicode_key_ignore_lossy_error() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let raw_key = {
+            let mut bytes = b"NUKEY_BAD".to_vec();
+            bytes.push(0xff);
+            bytes.extend_from_slice(b"NAME");
+            OsString::from_vec(bytes)
+        };
+
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var(&raw_key, "1");
+
+        let ignored = SafeEnvironment::with_prefix_and_filter("NUKEY", |_| true)
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert!(ignored.is_empty());
+
+        let lossy = SafeEnvironment::with_prefix_and_filter("NUKEY", |_| true)
+            .unwrap()
+            .on_non_unicode(OnNonUnicode::Lossy)
+            .collect()
+            .unwrap();
+        assert_eq!(lossy.len(), 1);
+        assert!(lossy.keys().next().unwrap().contains("bad"));
+
+        let errored = SafeEnvironment::with_prefix_and_filter("NUKEY", |_| true)
+            .unwrap()
+            .on_non_unicode(OnNonUnicode::Error)
+            .collect();
+
+        env::remove_var(&raw_key);
+
+        assert!(errored.is_err());
+    }
+
+    #[test]
+    fn with_parse_override_pins_a_numeric_looking_value_as_string() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("OVERRIDE_ZIP", "02139");
+
+        let collected = SafeEnvironment::with_prefix_and_filter("OVERRIDE", |_| true)
+            .unwrap()
+            .with_parse_override("zip", ParseKind::String)
+            .collect();
+
+        env::remove_var("OVERRIDE_ZIP");
+
+        let collected = collected.unwrap();
+        assert_eq!(collected.get("zip").unwrap().clone().into_string().unwrap(), "02139");
+    }
+
+    #[test]
+    fn with_parse_override_rejects_a_value_that_does_not_fit_the_pinned_kind() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("OVERRIDE_PORT", "not-a-number");
+
+        let collected = SafeEnvironment::with_prefix_and_filter("OVERRIDE", |_| true)
+            .unwrap()
+            .with_parse_override("port", ParseKind::I64)
+            .collect();
+
+        env::remove_var("OVERRIDE_PORT");
+
+        assert!(collected.is_err());
+    }
+
+    #[test]
+    fn with_parse_override_array_splits_on_the_list_separator() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("OVERRIDE_HOSTS", "a,b,c");
+
+        let collected = SafeEnvironment::with_prefix_and_filter("OVERRIDE", |_| true)
+            .unwrap()
+            .with_parse_override("hosts", ParseKind::Array)
+            .collect();
+
+        env::remove_var("OVERRIDE_HOSTS");
+
+        let collected = collected.unwrap();
+        let hosts = collected.get("hosts").unwrap().clone().into_array().unwrap();
+        let hosts: Vec<String> = hosts.into_iter().map(|v| v.into_string().unwrap()).collect();
+        assert_eq!(hosts, vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]);
+    }
+
+    #[test]
+    #[cfg(feature = "convert-case")]
+    fn with_parse_override_matches_the_translated_key() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        env::set_var("OVERRIDETR__ACCOUNT_NUMBER", "0010");
+
+        let collected = SafeEnvironment::with_prefix_and_filter("OVERRIDETR", |_| true)
+            .unwrap()
+            .translate_key(Case::Kebab)
+            .with_parse_override("account-number", ParseKind::String)
+            .collect();
+
+        env::remove_var("OVERRIDETR__ACCOUNT_NUMBER");
+
+        let collected = collected.unwrap();
+        assert_eq!(
+            collected.get("account-number").unwrap().clone().into_string().unwrap(),
+            "0010"
+        );
+    }
+}